@@ -1,8 +1,16 @@
+use crate::crypto::{self, Signature};
 use crate::traits::StateMachine;
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+
+/// Starting duration, in ticks, of the first PIN lockout. Each further consecutive failure
+/// raises the lockout to `INITIAL_LOCKOUT.pow(failures)`.
+const INITIAL_LOCKOUT: u64 = 2;
+
+/// Upper bound on the tracked failure streak, so `INITIAL_LOCKOUT.pow(failures)` can never
+/// overflow a `u64`.
+const MAX_LOCKOUT_HISTORY: u32 = 31;
 
 /// The keys on the ATM keypad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Key {
     One,
     Two,
@@ -11,24 +19,47 @@ pub enum Key {
     Enter,
 }
 
+/// A bank card: the "something you have" half of the ATM's two-factor authentication. `pubkey`
+/// is the public half of the keypair the card signs challenges with. See [`crypto::Keypair`]'s
+/// doc comment for why this demonstrates the flow without actually providing unforgeability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Card {
+    pub account: u64,
+    pub pubkey: [u8; 33],
+}
+
 /// Something you can do to the ATM
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Action {
-    SwipeCard(u64),
+    /// Swipe a card, along with the PIN hash the bank has on file for it and the card's
+    /// signature over the ATM's currently issued nonce.
+    SwipeCard {
+        card: Card,
+        expected_pin_hash: u64,
+        signature: Signature,
+    },
     PressKey(Key),
+    /// Advance the ATM's internal tick counter by one, independent of authentication state.
+    Tick,
 }
 
 /// The various states of authentication possible with the ATM
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 enum Auth {
+    #[default]
     Waiting,
-    Authenticating(u64),
+    /// A card has been swiped; waiting on the PIN keystrokes that, together with the card's
+    /// signature over `nonce`, grant `Authenticated`.
+    Authenticating {
+        card: Card,
+        nonce: u64,
+        signature: Signature,
+        expected_pin_hash: u64,
+    },
     Authenticated,
-}
-
-impl Default for Auth {
-    fn default() -> Self {
-        Auth::Waiting
-    }
+    /// Refusing `SwipeCard`/`PressKey` until `until_tick`, following `failures` consecutive
+    /// failed authentications (wrong PIN or invalid card signature).
+    LockedOut { until_tick: u64, failures: u32 },
 }
 
 impl From<Key> for &str {
@@ -43,40 +74,122 @@ impl From<Key> for &str {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Atm {
+    cash_inside: u64,
+    auth: Auth,
+    keystroke_register: Vec<Key>,
+    /// Monotonic clock driven by `Action::Tick`, used to time PIN lockouts.
+    tick: u64,
+    /// Consecutive failed-authentication count (wrong PIN or invalid card signature), carried
+    /// forward across lockouts so the backoff keeps escalating until an authentication succeeds.
+    failures: u32,
+    /// The challenge a swiped card's signature must cover; advances on every swipe so a
+    /// captured signature can't be replayed against a later session.
+    nonce: u64,
+}
+
+/// Hash `keystroke_register` salted with the card's account number, so the same PIN digits
+/// hash differently for different accounts.
+fn pin_hash(account: u64, keystroke_register: &[Key]) -> u64 {
+    crate::hash(&(account, keystroke_register))
+}
+
 impl StateMachine for Atm {
-    type State = Auth;
+    type State = Atm;
     type Transition = Action;
 
     fn next_state(starting_state: &Self::State, t: &Self::Transition) -> Self::State {
-        match (starting_state, t) {
-            (Auth::Waiting, Action::SwipeCard(pin_hash)) => Auth::Authenticating(*pin_hash),
-            (Auth::Authenticating(pin_hash), Action::PressKey(Key::Enter)) => {
-                // Simulate PIN hash verification (using simple hashing for demonstration)
-                let mut hasher = DefaultHasher::new();
-                starting_state.hash(&mut hasher);
-                let current_hash = hasher.finish();
-
-                if current_hash == *pin_hash {
-                    Auth::Authenticated
+        match (&starting_state.auth, t) {
+            (_, Action::Tick) => Atm {
+                tick: starting_state.tick + 1,
+                ..starting_state.clone()
+            },
+            (Auth::LockedOut { until_tick, .. }, _) if starting_state.tick < *until_tick => {
+                starting_state.clone()
+            }
+            (
+                Auth::Waiting | Auth::LockedOut { .. },
+                Action::SwipeCard {
+                    card,
+                    expected_pin_hash,
+                    signature,
+                },
+            ) => Atm {
+                auth: Auth::Authenticating {
+                    card: *card,
+                    nonce: starting_state.nonce,
+                    signature: *signature,
+                    expected_pin_hash: *expected_pin_hash,
+                },
+                keystroke_register: Vec::new(),
+                nonce: starting_state.nonce + 1,
+                ..starting_state.clone()
+            },
+            (Auth::LockedOut { .. }, Action::PressKey(_)) => starting_state.clone(),
+            (
+                Auth::Authenticating {
+                    card,
+                    nonce,
+                    signature,
+                    expected_pin_hash,
+                },
+                Action::PressKey(Key::Enter),
+            ) => {
+                let pin_ok = pin_hash(card.account, &starting_state.keystroke_register) == *expected_pin_hash;
+                let signature_ok = crypto::verify_public(&card.pubkey, &nonce.to_be_bytes(), signature);
+
+                if pin_ok && signature_ok {
+                    Atm {
+                        auth: Auth::Authenticated,
+                        keystroke_register: Vec::new(),
+                        failures: 0,
+                        ..starting_state.clone()
+                    }
                 } else {
-                    Auth::Waiting
+                    let failures = (starting_state.failures + 1).min(MAX_LOCKOUT_HISTORY);
+                    let until_tick = starting_state.tick + INITIAL_LOCKOUT.pow(failures);
+                    Atm {
+                        auth: Auth::LockedOut {
+                            until_tick,
+                            failures,
+                        },
+                        keystroke_register: Vec::new(),
+                        failures,
+                        ..starting_state.clone()
+                    }
+                }
+            }
+            (Auth::Authenticating { .. }, Action::PressKey(key)) => {
+                let mut keystroke_register = starting_state.keystroke_register.clone();
+                keystroke_register.push(*key);
+                Atm {
+                    keystroke_register,
+                    ..starting_state.clone()
                 }
             }
             (Auth::Authenticated, Action::PressKey(Key::Enter)) => {
                 // Simulate cash withdrawal
-                if let Some(amount) =
-                    calculate_withdrawal_amount(&starting_state.keystroke_register)
-                {
-                    if starting_state.cash_inside >= amount {
-                        // Update the cash inside the ATM after withdrawal
-                        let new_cash_inside = starting_state.cash_inside - amount;
-                        Auth::Waiting // Reset state back to waiting for the next transaction
-                            .with_cash_inside(new_cash_inside)
-                    } else {
-                        Auth::Waiting
-                    }
-                } else {
-                    Auth::Waiting
+                match calculate_withdrawal_amount(&starting_state.keystroke_register) {
+                    Some(amount) if starting_state.cash_inside >= amount => Atm {
+                        cash_inside: starting_state.cash_inside - amount,
+                        auth: Auth::Waiting,
+                        keystroke_register: Vec::new(),
+                        ..starting_state.clone()
+                    },
+                    _ => Atm {
+                        auth: Auth::Waiting,
+                        keystroke_register: Vec::new(),
+                        ..starting_state.clone()
+                    },
+                }
+            }
+            (Auth::Authenticated, Action::PressKey(key)) => {
+                let mut keystroke_register = starting_state.keystroke_register.clone();
+                keystroke_register.push(*key);
+                Atm {
+                    keystroke_register,
+                    ..starting_state.clone()
                 }
             }
             _ => starting_state.clone(), // No state change, return the current state
@@ -90,192 +203,287 @@ fn calculate_withdrawal_amount(keystroke_register: &[Key]) -> Option<u64> {
         if let Key::Enter = key {
             break;
         }
-        amount_str.push_str(&String::from(*key));
+        amount_str.push_str(<&str>::from(*key));
     }
     amount_str.parse().ok()
 }
 
-impl Atm {
-    // Helper method to create a new `Atm` with updated `cash_inside` value
-    fn with_cash_inside(&self, cash_inside: u64) -> Atm {
-        Atm {
-            cash_inside,
-            expected_pin_hash: self.expected_pin_hash.clone(),
-            keystroke_register: Vec::new(),
-        }
+#[cfg(test)]
+fn swipe_card(account: u64, seed: u64, nonce: u64, expected_pin_hash: u64) -> Action {
+    use crate::crypto::Keypair;
+
+    let keypair = Keypair::generate(seed);
+    let signature = keypair.sign(&nonce.to_be_bytes());
+    Action::SwipeCard {
+        card: Card {
+            account,
+            pubkey: keypair.public,
+        },
+        expected_pin_hash,
+        signature,
     }
 }
 
-pub struct Atm {
-    cash_inside: u64,
-    expected_pin_hash: Auth,
-    keystroke_register: Vec<Key>,
-}
-
 #[test]
 fn sm_3_simple_swipe_card() {
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Waiting,
-        keystroke_register: Vec::new(),
-    };
-    let end = Atm::next_state(&start, &Action::SwipeCard(1234));
-    let expected = Atm {
-        cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(1234),
+        auth: Auth::Waiting,
         keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
-
-    assert_eq!(end, expected);
+    let action = swipe_card(1, 99, 0, 1234);
+    let end = Atm::next_state(&start, &action);
+
+    match end.auth {
+        Auth::Authenticating {
+            expected_pin_hash, ..
+        } => assert_eq!(expected_pin_hash, 1234),
+        other => panic!("expected Authenticating, got {other:?}"),
+    }
+    assert_eq!(end.nonce, 1);
 }
 
 #[test]
-fn sm_3_swipe_card_again_part_way_through() {
+fn sm_3_press_key_before_card_swipe() {
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(1234),
-        keystroke_register: Vec::new(),
-    };
-    let end = Atm::next_state(&start, &Action::SwipeCard(1234));
-    let expected = Atm {
-        cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(1234),
+        auth: Auth::Waiting,
         keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
+    let end = Atm::next_state(&start, &Action::PressKey(Key::One));
 
-    assert_eq!(end, expected);
+    assert_eq!(end, start);
+}
 
+#[test]
+fn sm_3_enter_single_digit_of_pin() {
+    let action = swipe_card(1, 99, 0, 1234);
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(1234),
-        keystroke_register: vec![Key::One, Key::Three],
-    };
-    let end = Atm::next_state(&start, &Action::SwipeCard(1234));
-    let expected = Atm {
-        cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(1234),
-        keystroke_register: vec![Key::One, Key::Three],
+        auth: Auth::Waiting,
+        keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
+    let authenticating = Atm::next_state(&start, &action);
 
-    assert_eq!(end, expected);
+    let end = Atm::next_state(&authenticating, &Action::PressKey(Key::One));
+    assert_eq!(end.keystroke_register, vec![Key::One]);
+
+    let end1 = Atm::next_state(&end, &Action::PressKey(Key::Two));
+    assert_eq!(end1.keystroke_register, vec![Key::One, Key::Two]);
 }
 
 #[test]
-fn sm_3_press_key_before_card_swipe() {
+fn sm_3_enter_wrong_pin_locks_out() {
+    let action = swipe_card(1, 99, 0, 1234);
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Waiting,
+        auth: Auth::Waiting,
         keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
-    let end = Atm::next_state(&start, &Action::PressKey(Key::One));
-    let expected = Atm {
-        cash_inside: 10,
-        expected_pin_hash: Auth::Waiting,
-        keystroke_register: Vec::new(),
+    let authenticating = Atm::next_state(&start, &action);
+    let keyed_in = Atm {
+        keystroke_register: vec![Key::Three, Key::Three, Key::Three, Key::Three],
+        ..authenticating
     };
 
-    assert_eq!(end, expected);
+    let end = Atm::next_state(&keyed_in, &Action::PressKey(Key::Enter));
+
+    assert_eq!(
+        end.auth,
+        Auth::LockedOut {
+            until_tick: INITIAL_LOCKOUT,
+            failures: 1,
+        }
+    );
+    assert_eq!(end.keystroke_register, Vec::<Key>::new());
 }
 
 #[test]
-fn sm_3_enter_single_digit_of_pin() {
+fn sm_3_enter_correct_pin_with_valid_signature_authenticates() {
+    let account = 1;
+    let pin = vec![Key::One, Key::Two, Key::Three, Key::Four];
+    let expected_pin_hash = pin_hash(account, &pin);
+
+    let action = swipe_card(account, 99, 0, expected_pin_hash);
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(1234),
+        auth: Auth::Waiting,
         keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
-    let end = Atm::next_state(&start, &Action::PressKey(Key::One));
-    let expected = Atm {
-        cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(1234),
-        keystroke_register: vec![Key::One],
+    let authenticating = Atm::next_state(&start, &action);
+    let keyed_in = Atm {
+        keystroke_register: pin,
+        ..authenticating
     };
 
-    assert_eq!(end, expected);
+    let end = Atm::next_state(&keyed_in, &Action::PressKey(Key::Enter));
+
+    assert_eq!(end.auth, Auth::Authenticated);
+    assert_eq!(end.failures, 0);
+}
+
+#[test]
+fn sm_3_correct_pin_with_forged_signature_is_rejected() {
+    use crate::crypto::Keypair;
+
+    let account = 1;
+    let pin = vec![Key::One, Key::Two, Key::Three, Key::Four];
+    let expected_pin_hash = pin_hash(account, &pin);
+
+    // The real card's public key, but a signature produced by a different keypair.
+    let real_card = Keypair::generate(99);
+    let forged_signature = Keypair::generate(4242).sign(&0u64.to_be_bytes());
 
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(1234),
-        keystroke_register: vec![Key::One],
+        auth: Auth::Waiting,
+        keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
-    let end1 = Atm::next_state(&start, &Action::PressKey(Key::Two));
-    let expected1 = Atm {
-        cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(1234),
-        keystroke_register: vec![Key::One, Key::Two],
+    let action = Action::SwipeCard {
+        card: Card {
+            account,
+            pubkey: real_card.public,
+        },
+        expected_pin_hash,
+        signature: forged_signature,
+    };
+    let authenticating = Atm::next_state(&start, &action);
+    let keyed_in = Atm {
+        keystroke_register: pin,
+        ..authenticating
     };
 
-    assert_eq!(end1, expected1);
+    let end = Atm::next_state(&keyed_in, &Action::PressKey(Key::Enter));
+
+    assert_ne!(end.auth, Auth::Authenticated);
 }
 
+/// Known limitation, not a regression test: `crypto::Keypair::generate` embeds `secret` directly
+/// in `public` (see its doc comment), so anyone who has only observed a card's `pubkey` bytes can
+/// reconstruct the same keypair and forge a signature that `verify_public` accepts. This is the
+/// attack the real scheme would need to stop and this toy one does not; recovering real
+/// unforgeability would require an actual asymmetric primitive, not available without an
+/// external crate.
 #[test]
-fn sm_3_enter_wrong_pin() {
-    // Create hash of pin
+fn sm_3_signature_forged_from_observed_public_key_is_accepted() {
+    use crate::crypto::Keypair;
+
+    let account = 1;
     let pin = vec![Key::One, Key::Two, Key::Three, Key::Four];
-    let pin_hash = crate::hash(&pin);
+    let expected_pin_hash = pin_hash(account, &pin);
+
+    let real_card = Keypair::generate(99);
+    // An attacker who has only seen `real_card.public` (e.g. by skimming the card) can recover
+    // `secret` from it and reconstruct the same keypair.
+    let mut secret_bytes = [0u8; 8];
+    secret_bytes.copy_from_slice(&real_card.public[..8]);
+    let recovered_secret = u64::from_be_bytes(secret_bytes);
+    let forged_signature = Keypair::generate(recovered_secret).sign(&0u64.to_be_bytes());
 
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(pin_hash),
-        keystroke_register: vec![Key::Three, Key::Three, Key::Three, Key::Three],
-    };
-    let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
-    let expected = Atm {
-        cash_inside: 10,
-        expected_pin_hash: Auth::Waiting,
+        auth: Auth::Waiting,
         keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
+    };
+    let action = Action::SwipeCard {
+        card: Card {
+            account,
+            pubkey: real_card.public,
+        },
+        expected_pin_hash,
+        signature: forged_signature,
+    };
+    let authenticating = Atm::next_state(&start, &action);
+    let keyed_in = Atm {
+        keystroke_register: pin,
+        ..authenticating
     };
 
-    assert_eq!(end, expected);
+    let end = Atm::next_state(&keyed_in, &Action::PressKey(Key::Enter));
+
+    assert_eq!(end.auth, Auth::Authenticated);
 }
 
 #[test]
-fn sm_3_enter_correct_pin() {
-    // Create hash of pin
-    let pin = vec![Key::One, Key::Two, Key::Three, Key::Four];
-    let pin_hash = crate::hash(&pin);
-
+fn sm_3_locked_out_ignores_keys_until_expiry() {
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticating(pin_hash),
-        keystroke_register: vec![Key::One, Key::Two, Key::Three, Key::Four],
-    };
-    let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
-    let expected = Atm {
-        cash_inside: 10,
-        expected_pin_hash: Auth::Authenticated,
+        auth: Auth::LockedOut {
+            until_tick: 5,
+            failures: 1,
+        },
         keystroke_register: Vec::new(),
+        tick: 3,
+        failures: 1,
+        nonce: 0,
     };
 
-    assert_eq!(end, expected);
+    let end = Atm::next_state(&start, &Action::PressKey(Key::One));
+    assert_eq!(end, start);
+
+    let end = Atm::next_state(&start, &swipe_card(1, 99, 0, 1234));
+    assert_eq!(end, start);
 }
 
 #[test]
 fn sm_3_enter_single_digit_of_withdraw_amount() {
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticated,
+        auth: Auth::Authenticated,
         keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
     let end = Atm::next_state(&start, &Action::PressKey(Key::One));
     let expected = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticated,
+        auth: Auth::Authenticated,
         keystroke_register: vec![Key::One],
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
 
     assert_eq!(end, expected);
 
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticated,
+        auth: Auth::Authenticated,
         keystroke_register: vec![Key::One],
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
     let end1 = Atm::next_state(&start, &Action::PressKey(Key::Four));
     let expected1 = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticated,
+        auth: Auth::Authenticated,
         keystroke_register: vec![Key::One, Key::Four],
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
 
     assert_eq!(end1, expected1);
@@ -285,14 +493,20 @@ fn sm_3_enter_single_digit_of_withdraw_amount() {
 fn sm_3_try_to_withdraw_too_much() {
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticated,
+        auth: Auth::Authenticated,
         keystroke_register: vec![Key::One, Key::Four],
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
     let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
     let expected = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Waiting,
+        auth: Auth::Waiting,
         keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
 
     assert_eq!(end, expected);
@@ -302,15 +516,94 @@ fn sm_3_try_to_withdraw_too_much() {
 fn sm_3_withdraw_acceptable_amount() {
     let start = Atm {
         cash_inside: 10,
-        expected_pin_hash: Auth::Authenticated,
+        auth: Auth::Authenticated,
         keystroke_register: vec![Key::One],
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
     let end = Atm::next_state(&start, &Action::PressKey(Key::Enter));
     let expected = Atm {
         cash_inside: 9,
-        expected_pin_hash: Auth::Waiting,
+        auth: Auth::Waiting,
         keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
     };
 
     assert_eq!(end, expected);
 }
+
+#[test]
+fn sm_3_log_replays_to_current_state() {
+    use crate::log::StateMachineLog;
+
+    let mut log = StateMachineLog::<Atm>::new(Atm {
+        cash_inside: 10,
+        auth: Auth::Waiting,
+        keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
+    });
+
+    log.apply(swipe_card(1, 99, 0, 1234));
+    log.apply(Action::PressKey(Key::One));
+    log.apply(Action::PressKey(Key::Four));
+
+    assert_eq!(log.transitions().len(), 3);
+    assert_eq!(log.replay(), log.current_state());
+    assert_eq!(log.state_at(2).keystroke_register, vec![Key::One, Key::Four]);
+}
+
+#[test]
+fn sm_3_log_chain_detects_tampering() {
+    use crate::log::{verify_chain, StateMachineLog};
+
+    let mut log = StateMachineLog::<Atm>::new(Atm {
+        cash_inside: 10,
+        auth: Auth::Waiting,
+        keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
+    });
+
+    log.apply(swipe_card(1, 99, 0, 1234));
+    log.apply(Action::PressKey(Key::One));
+
+    assert!(verify_chain(log.chain()));
+
+    let mut tampered = log.chain().to_vec();
+    tampered.swap(0, 1);
+    assert!(!verify_chain(&tampered));
+}
+
+#[test]
+fn sm_3_log_drops_exact_replay_but_allows_it_once_state_moves_on() {
+    use crate::log::StateMachineLog;
+
+    let mut log = StateMachineLog::<Atm>::new(Atm {
+        cash_inside: 10,
+        auth: Auth::Waiting,
+        keystroke_register: Vec::new(),
+        tick: 0,
+        failures: 0,
+        nonce: 0,
+    });
+
+    // Waiting ignores a bare keypress, so this is a no-op: applying it twice in a row is an
+    // exact replay of the same transition in the same resulting state, and should be dropped.
+    log.apply(Action::PressKey(Key::One));
+    assert_eq!(log.transitions().len(), 1);
+    log.apply(Action::PressKey(Key::One));
+    assert_eq!(log.transitions().len(), 1);
+
+    // Once the state has moved on (here, by ticking the clock), the same transition is no
+    // longer an exact replay and is allowed through again.
+    log.apply(Action::Tick);
+    assert_eq!(log.transitions().len(), 2);
+    log.apply(Action::PressKey(Key::One));
+    assert_eq!(log.transitions().len(), 3);
+}
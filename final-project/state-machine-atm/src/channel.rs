@@ -0,0 +1,207 @@
+use crate::traits::StateMachine;
+
+/// The states of a two-party off-chain payment channel, modeled on the customer/merchant
+/// channel in libbolt: both sides fund the channel once on open, exchange any number of
+/// off-chain `Pay` transitions that only move the in-memory balance split, then settle
+/// on-chain once via `Close`/`Finalize`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Channel {
+    Uninit,
+    Open {
+        cust_balance: i64,
+        merch_balance: i64,
+        seq: u64,
+    },
+    Closing {
+        cust_balance: i64,
+        merch_balance: i64,
+        seq: u64,
+    },
+    Closed,
+}
+
+/// Something that can happen to a payment channel.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Transition {
+    Init { cust: i64, merch: i64 },
+    /// Move `amount` from the customer's balance to the merchant's.
+    Pay { amount: i64 },
+    Close,
+    Finalize,
+}
+
+impl StateMachine for Channel {
+    type State = Channel;
+    type Transition = Transition;
+
+    fn next_state(starting_state: &Self::State, t: &Self::Transition) -> Self::State {
+        match (starting_state, t) {
+            (Channel::Uninit, Transition::Init { cust, merch }) => Channel::Open {
+                cust_balance: *cust,
+                merch_balance: *merch,
+                seq: 0,
+            },
+            (
+                Channel::Open {
+                    cust_balance,
+                    merch_balance,
+                    seq,
+                },
+                Transition::Pay { amount },
+            ) => {
+                if *amount < 0 {
+                    return starting_state.clone();
+                }
+                match (cust_balance.checked_sub(*amount), merch_balance.checked_add(*amount)) {
+                    (Some(new_cust_balance), Some(new_merch_balance))
+                        if new_cust_balance >= 0 && new_merch_balance >= 0 =>
+                    {
+                        Channel::Open {
+                            cust_balance: new_cust_balance,
+                            merch_balance: new_merch_balance,
+                            seq: seq + 1,
+                        }
+                    }
+                    _ => starting_state.clone(),
+                }
+            }
+            (
+                Channel::Open {
+                    cust_balance,
+                    merch_balance,
+                    seq,
+                },
+                Transition::Close,
+            ) => Channel::Closing {
+                cust_balance: *cust_balance,
+                merch_balance: *merch_balance,
+                seq: *seq,
+            },
+            (Channel::Closing { .. }, Transition::Finalize) => Channel::Closed,
+            _ => starting_state.clone(),
+        }
+    }
+}
+
+#[test]
+fn sm_4_init_opens_channel() {
+    let end = Channel::next_state(&Channel::Uninit, &Transition::Init { cust: 10, merch: 5 });
+
+    assert_eq!(
+        end,
+        Channel::Open {
+            cust_balance: 10,
+            merch_balance: 5,
+            seq: 0,
+        }
+    );
+}
+
+#[test]
+fn sm_4_pay_moves_balance_and_bumps_seq() {
+    let start = Channel::Open {
+        cust_balance: 10,
+        merch_balance: 5,
+        seq: 0,
+    };
+    let end = Channel::next_state(&start, &Transition::Pay { amount: 3 });
+
+    assert_eq!(
+        end,
+        Channel::Open {
+            cust_balance: 7,
+            merch_balance: 8,
+            seq: 1,
+        }
+    );
+}
+
+#[test]
+fn sm_4_pay_preserves_total_balance() {
+    let start = Channel::Open {
+        cust_balance: 10,
+        merch_balance: 5,
+        seq: 0,
+    };
+    let end = Channel::next_state(&start, &Transition::Pay { amount: 3 });
+
+    if let Channel::Open {
+        cust_balance,
+        merch_balance,
+        ..
+    } = end
+    {
+        assert_eq!(cust_balance + merch_balance, 15);
+    } else {
+        panic!("expected Open");
+    }
+}
+
+#[test]
+fn sm_4_pay_rejects_overdraft() {
+    let start = Channel::Open {
+        cust_balance: 10,
+        merch_balance: 5,
+        seq: 0,
+    };
+    let end = Channel::next_state(&start, &Transition::Pay { amount: 11 });
+
+    assert_eq!(end, start);
+}
+
+#[test]
+fn sm_4_pay_rejects_negative_amount() {
+    let start = Channel::Open {
+        cust_balance: 5,
+        merch_balance: 5,
+        seq: 0,
+    };
+    let end = Channel::next_state(&start, &Transition::Pay { amount: -3 });
+
+    assert_eq!(end, start);
+}
+
+#[test]
+fn sm_4_pay_rejects_amount_that_would_overflow() {
+    let start = Channel::Open {
+        cust_balance: 0,
+        merch_balance: 0,
+        seq: 0,
+    };
+    let end = Channel::next_state(&start, &Transition::Pay { amount: i64::MIN });
+
+    assert_eq!(end, start);
+}
+
+#[test]
+fn sm_4_close_then_finalize() {
+    let open = Channel::Open {
+        cust_balance: 7,
+        merch_balance: 8,
+        seq: 1,
+    };
+    let closing = Channel::next_state(&open, &Transition::Close);
+    assert_eq!(
+        closing,
+        Channel::Closing {
+            cust_balance: 7,
+            merch_balance: 8,
+            seq: 1,
+        }
+    );
+
+    let closed = Channel::next_state(&closing, &Transition::Finalize);
+    assert_eq!(closed, Channel::Closed);
+}
+
+#[test]
+fn sm_4_open_cannot_finalize_directly() {
+    let open = Channel::Open {
+        cust_balance: 7,
+        merch_balance: 8,
+        seq: 1,
+    };
+    let end = Channel::next_state(&open, &Transition::Finalize);
+
+    assert_eq!(end, open);
+}
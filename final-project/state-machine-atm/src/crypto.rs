@@ -0,0 +1,45 @@
+use crate::hash;
+
+/// A lightweight stand-in for a real signature scheme, shaped like the ethkey
+/// generate-keypair / sign-message / verify-against-public-key surface, so the ATM's card
+/// authentication exercises the same "something you have" flow without pulling in an external
+/// crypto crate. Like the rest of the ATM's hashing, `public` encodes the secret directly
+/// rather than through a one-way curve operation, so anyone who observes `public` can recover
+/// `secret` and forge signatures for it — this does not close the real authentication hole, it
+/// only demonstrates the shape of the flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Keypair {
+    secret: u64,
+    pub public: [u8; 33],
+}
+
+impl Keypair {
+    /// Generate a keypair from a caller-supplied secret (the crate has no secure randomness
+    /// source, so unlike a real keygen this does not pick the secret itself).
+    pub fn generate(secret: u64) -> Self {
+        let mut public = [0u8; 33];
+        public[..8].copy_from_slice(&secret.to_be_bytes());
+        Keypair { secret, public }
+    }
+
+    /// Sign `message` with this keypair's secret.
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        Signature(hash(&(self.secret, message.to_vec())))
+    }
+}
+
+/// A signature produced by [`Keypair::sign`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Signature(u64);
+
+/// Verify that `signature` over `message` was produced by the holder of `public`.
+///
+/// Because `public` embeds `secret` directly (see [`Keypair`]), this check can be passed by
+/// anyone who has merely observed `public`, not only by the original secret's holder — it does
+/// not provide the unforgeability a real public-key signature would.
+pub fn verify_public(public: &[u8; 33], message: &[u8], signature: &Signature) -> bool {
+    let mut secret_bytes = [0u8; 8];
+    secret_bytes.copy_from_slice(&public[..8]);
+    let secret = u64::from_be_bytes(secret_bytes);
+    hash(&(secret, message.to_vec())) == signature.0
+}
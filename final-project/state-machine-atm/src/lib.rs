@@ -0,0 +1,19 @@
+pub mod atm;
+pub mod channel;
+pub mod crypto;
+pub mod log;
+pub mod traits;
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+pub use log::StateMachineLog;
+pub use traits::StateMachine;
+
+/// Hash any `Hash`-able value with the crate's default hasher. Shared by every state machine in
+/// the crate so PIN hashes, transition hashes, etc. are all computed the same way.
+pub fn hash<T: Hash>(t: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    t.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,161 @@
+use crate::traits::StateMachine;
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+
+/// Capacity of the recently-seen-transition ring kept by [`StateMachineLog::apply`], modeled on
+/// Solana's recent-blockhash cache: once full, the oldest entry is evicted to make room for the
+/// newest.
+const MAX_SEEN: usize = 1024;
+
+/// One link in the hash chain built up by [`StateMachineLog::apply`]. `state_hash` commits to
+/// the state produced by `transition_hash`, and `prev_hash` commits to the entry before it, so
+/// the chain is tamper-evident: reordering or editing any recorded transition changes every
+/// `prev_hash` that follows it. The genesis entry (the first one ever applied) always has
+/// `prev_hash == 0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub prev_hash: u64,
+    pub transition_hash: u64,
+    pub state_hash: u64,
+}
+
+impl Entry {
+    /// `hash(prev_hash ++ transition_hash ++ state_hash)`, i.e. this entry's own identity in the
+    /// chain, which becomes the next entry's `prev_hash`.
+    fn hash(&self) -> u64 {
+        crate::hash(&(self.prev_hash, self.transition_hash, self.state_hash))
+    }
+}
+
+/// Recompute each link of `entries` and confirm it chains back to the genesis entry, detecting
+/// reordering, or tampering with any entry that has a later entry linking to it. Only the
+/// `prev_hash` links are checked: an edit to the last entry's `transition_hash` or `state_hash`,
+/// or entries dropped from the end, are not detected.
+pub fn verify_chain(entries: &[Entry]) -> bool {
+    let mut expected_prev_hash = 0;
+    for entry in entries {
+        if entry.prev_hash != expected_prev_hash {
+            return false;
+        }
+        expected_prev_hash = entry.hash();
+    }
+    true
+}
+
+/// An append-only record of every `(State, Transition, State)` triple applied to a state
+/// machine through [`StateMachineLog::apply`], so the current state is always derivable by
+/// replaying the recorded transitions from the genesis state. This mirrors the blockchain
+/// framing that the chain itself is nothing more than the genesis state plus the complete
+/// ordered history of transitions.
+pub struct StateMachineLog<M: StateMachine> {
+    genesis: M::State,
+    transitions: Vec<M::Transition>,
+    /// `states[i]` is the state produced by applying `transitions[i]`.
+    states: Vec<M::State>,
+    /// `chain[i]` is the hash-chained entry produced by applying `transitions[i]`.
+    chain: Vec<Entry>,
+    /// The `MAX_SEEN` most recently applied `hash(transition ++ current_state_hash)` keys, in
+    /// insertion order, so a duplicate application of the same transition in the same state can
+    /// be detected and dropped as a replay.
+    seen_order: VecDeque<u64>,
+    seen: HashSet<u64>,
+}
+
+impl<M: StateMachine> StateMachineLog<M>
+where
+    M::State: Clone,
+{
+    /// Start a new, empty log rooted at `genesis`.
+    pub fn new(genesis: M::State) -> Self {
+        StateMachineLog {
+            genesis,
+            transitions: Vec::new(),
+            states: Vec::new(),
+            chain: Vec::new(),
+            seen_order: VecDeque::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// The state the machine is in right now, i.e. after folding every recorded transition.
+    pub fn current_state(&self) -> M::State {
+        self.states.last().cloned().unwrap_or_else(|| self.genesis.clone())
+    }
+
+    /// Recompute the state from scratch by folding every recorded transition over the genesis
+    /// state. Should always agree with [`StateMachineLog::current_state`]; exists so the log can
+    /// be audited independently of the running tally kept by `apply`.
+    pub fn replay(&self) -> M::State {
+        self.transitions
+            .iter()
+            .fold(self.genesis.clone(), |state, t| M::next_state(&state, t))
+    }
+
+    /// The state immediately after the transition at `index` was applied.
+    pub fn state_at(&self, index: usize) -> M::State {
+        self.states[index].clone()
+    }
+
+    /// The complete ordered history of transitions applied so far.
+    pub fn transitions(&self) -> &[M::Transition] {
+        &self.transitions
+    }
+}
+
+impl<M: StateMachine> StateMachineLog<M>
+where
+    M::State: Clone + Hash,
+    M::Transition: Hash,
+{
+    /// Apply `t` to the current state, appending the resulting triple to the log and a new
+    /// hash-chained [`Entry`] linking it to the entry before it. Callers should use this instead
+    /// of calling `M::next_state` directly, so every transition the machine ever makes is
+    /// recorded and tamper-evident.
+    ///
+    /// If `t` applied to the current state is an exact replay of a transition already recorded
+    /// recently in the same state, it is silently dropped instead: mixing the current state's
+    /// hash into the replay key means a legitimately repeated transition is still allowed once
+    /// the state has moved on.
+    pub fn apply(&mut self, t: M::Transition) {
+        let before = self.current_state();
+        let replay_key = crate::hash(&(&t, crate::hash(&before)));
+        if self.seen.contains(&replay_key) {
+            return;
+        }
+
+        let after = M::next_state(&before, &t);
+
+        let prev_hash = self.chain.last().map(Entry::hash).unwrap_or(0);
+        let entry = Entry {
+            prev_hash,
+            transition_hash: crate::hash(&t),
+            state_hash: crate::hash(&after),
+        };
+
+        self.transitions.push(t);
+        self.states.push(after);
+        self.chain.push(entry);
+        self.remember(replay_key);
+    }
+
+    /// The complete hash chain built up by every call to [`StateMachineLog::apply`] so far.
+    pub fn chain(&self) -> &[Entry] {
+        &self.chain
+    }
+
+    /// Whether `replay_key` (a `hash(transition ++ current_state_hash)`, as computed by
+    /// `apply`) is still within the recently-seen ring.
+    pub fn seen(&self, replay_key: &u64) -> bool {
+        self.seen.contains(replay_key)
+    }
+
+    fn remember(&mut self, replay_key: u64) {
+        if self.seen_order.len() >= MAX_SEEN {
+            if let Some(oldest) = self.seen_order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen_order.push_back(replay_key);
+        self.seen.insert(replay_key);
+    }
+}
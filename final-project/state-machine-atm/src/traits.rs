@@ -0,0 +1,11 @@
+/// A state machine is defined by a starting state, a transition function, and the states that
+/// transition function can produce.
+pub trait StateMachine {
+    /// The states that can be occupied by this machine.
+    type State;
+    /// The transitions that can be made between states.
+    type Transition;
+
+    /// Calculate the resulting state of putting a given state through a given transition.
+    fn next_state(starting_state: &Self::State, t: &Self::Transition) -> Self::State;
+}